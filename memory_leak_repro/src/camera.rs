@@ -0,0 +1,114 @@
+use glam::{Mat4, Vec3};
+
+/// A simple fly-style camera: position plus yaw/pitch, from which the view
+/// and projection matrices are derived on demand.
+pub struct Camera {
+    pub position: Vec3,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub fov_y_radians: f32,
+    pub aspect: f32,
+    pub near: f32,
+    pub far: f32,
+}
+
+impl Camera {
+    pub fn new(aspect: f32) -> Self {
+        Self {
+            position: Vec3::new(0.0, 0.0, 3.0),
+            yaw: -90f32.to_radians(),
+            pitch: 0.0,
+            fov_y_radians: 45f32.to_radians(),
+            aspect,
+            near: 0.1,
+            far: 100.0,
+        }
+    }
+
+    pub fn set_aspect(&mut self, width: u32, height: u32) {
+        self.aspect = width.max(1) as f32 / height.max(1) as f32;
+    }
+
+    fn forward(&self) -> Vec3 {
+        Vec3::new(
+            self.yaw.cos() * self.pitch.cos(),
+            self.pitch.sin(),
+            self.yaw.sin() * self.pitch.cos(),
+        )
+        .normalize()
+    }
+
+    pub fn view_projection_matrix(&self) -> Mat4 {
+        let view = Mat4::look_to_rh(self.position, self.forward(), Vec3::Y);
+        let projection = Mat4::perspective_rh(self.fov_y_radians, self.aspect, self.near, self.far);
+        projection * view
+    }
+}
+
+/// POD layout uploaded to the camera's uniform buffer each frame.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct CameraUniform {
+    pub view_proj: [[f32; 4]; 4],
+}
+
+impl CameraUniform {
+    pub fn from_camera(camera: &Camera) -> Self {
+        Self { view_proj: camera.view_projection_matrix().to_cols_array_2d() }
+    }
+}
+
+/// Owns the camera's bind group layout plus one uniform buffer/bind group
+/// per in-flight frame, so writing this frame's matrix never stalls on a
+/// buffer the GPU might still be reading from a previous frame.
+pub struct CameraBindings {
+    pub bind_group_layout: wgpu::BindGroupLayout,
+    buffers: Vec<wgpu::Buffer>,
+    bind_groups: Vec<wgpu::BindGroup>,
+}
+
+impl CameraBindings {
+    pub fn new(device: &wgpu::Device, frames_in_flight: usize) -> Self {
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Camera BindGroupLayout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let mut buffers = Vec::with_capacity(frames_in_flight);
+        let mut bind_groups = Vec::with_capacity(frames_in_flight);
+        for i in 0..frames_in_flight {
+            let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some(&format!("Camera Uniform {i}")),
+                size: std::mem::size_of::<CameraUniform>() as u64,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some(&format!("Camera BindGroup {i}")),
+                layout: &bind_group_layout,
+                entries: &[wgpu::BindGroupEntry { binding: 0, resource: buffer.as_entire_binding() }],
+            });
+            buffers.push(buffer);
+            bind_groups.push(bind_group);
+        }
+
+        Self { bind_group_layout, buffers, bind_groups }
+    }
+
+    /// Uploads `uniform` into the slot for `frame_count` and returns that
+    /// slot's bind group for passes to bind.
+    pub fn update(&self, queue: &wgpu::Queue, frame_count: u64, uniform: CameraUniform) -> &wgpu::BindGroup {
+        let slot = frame_count as usize % self.buffers.len();
+        queue.write_buffer(&self.buffers[slot], 0, bytemuck::bytes_of(&uniform));
+        &self.bind_groups[slot]
+    }
+}
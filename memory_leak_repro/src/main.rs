@@ -1,45 +1,213 @@
+mod camera;
+mod post_process;
+mod render_target;
+mod renderer;
+
 use std::sync::Arc;
 use std::time::Instant;
+use camera::{Camera, CameraBindings, CameraUniform};
+use post_process::PostProcessChain;
+use render_target::{OffscreenRenderTarget, RenderTarget, SurfaceRenderTarget};
+use renderer::{FrameData, Phase, RenderPass, Renderer};
 use winit::{
     application::ApplicationHandler,
-    event::WindowEvent,
+    event::{ElementState, KeyEvent, WindowEvent},
     event_loop::{ActiveEventLoop, ControlFlow, EventLoop},
+    keyboard::{KeyCode, PhysicalKey},
     window::{Window, WindowId},
 };
 
+/// The demo's foreground pass: draws a triangle positioned in world space
+/// through the camera's view-projection matrix, on top of what the renderer's
+/// clear to `clear_color` and [`BackgroundPass`] left in the view.
+struct TrianglePass {
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl RenderPass for TrianglePass {
+    fn phase(&self) -> Phase {
+        Phase::Opaque
+    }
+
+    fn record(&self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView, frame: &FrameData<'_>) {
+        // Tag the pass with the frame it belongs to so a GPU debugger
+        // (RenderDoc, Xcode) can line captures up against the CPU-side
+        // frame counter and timeline.
+        encoder.insert_debug_marker(&format!("Triangle frame={} elapsed={:?}", frame.frame_count, frame.elapsed));
+
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Triangle"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, frame.camera_bind_group, &[]);
+        pass.draw(0..3, 0..1);
+    }
+}
+
+/// A second, independent pass drawn behind the triangle. It exists partly
+/// for its own sake (a backdrop gradient) and partly so `Renderer::render`'s
+/// parallel chunk-recording path always has more than one item to split
+/// across worker threads instead of degenerating to a single chunk.
+struct BackgroundPass {
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl RenderPass for BackgroundPass {
+    fn phase(&self) -> Phase {
+        Phase::Background
+    }
+
+    fn record(&self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView, _frame: &FrameData<'_>) {
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Background"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        pass.set_pipeline(&self.pipeline);
+        pass.draw(0..3, 0..1);
+    }
+}
+
+/// A single graphics backend we can ask wgpu to try.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RenderBackend {
+    Vulkan,
+    Metal,
+    Dx12,
+    Gl,
+}
+
+impl RenderBackend {
+    fn to_wgpu(self) -> wgpu::Backends {
+        match self {
+            RenderBackend::Vulkan => wgpu::Backends::VULKAN,
+            RenderBackend::Metal => wgpu::Backends::METAL,
+            RenderBackend::Dx12 => wgpu::Backends::DX12,
+            RenderBackend::Gl => wgpu::Backends::GL,
+        }
+    }
+}
+
+/// Backend selection policy: try `preferred` first, then walk `fallbacks`
+/// in order until `request_adapter` succeeds.
+struct BackendConfig {
+    preferred: RenderBackend,
+    fallbacks: Vec<RenderBackend>,
+    power_preference: wgpu::PowerPreference,
+    force_fallback_adapter: bool,
+}
+
+impl Default for BackendConfig {
+    fn default() -> Self {
+        Self {
+            preferred: RenderBackend::Vulkan,
+            fallbacks: vec![RenderBackend::Metal, RenderBackend::Dx12, RenderBackend::Gl],
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            force_fallback_adapter: false,
+        }
+    }
+}
+
 struct App {
     window: Option<Arc<Window>>,
     surface: Option<wgpu::Surface<'static>>,
-    device: Option<wgpu::Device>,
-    queue: Option<wgpu::Queue>,
-    config: Option<wgpu::SurfaceConfiguration>,
-    render_pipeline: Option<wgpu::RenderPipeline>,
+    renderer: Option<Renderer>,
+    backend_config: BackendConfig,
+    post_process_preset: Option<String>,
+    post_process: Option<PostProcessChain>,
+    scene_target: Option<OffscreenRenderTarget>,
+    screenshot_path: Option<String>,
+    camera: Camera,
+    camera_bindings: Option<CameraBindings>,
     start_time: Instant,
     frame_count: u64,
     last_report: Instant,
 }
 
 impl App {
-    fn new() -> Self {
+    fn with_backend_config(backend_config: BackendConfig) -> Self {
         Self {
-            window: None, surface: None, device: None, queue: None,
-            config: None, render_pipeline: None,
+            window: None, surface: None, renderer: None, backend_config,
+            post_process_preset: None, post_process: None, scene_target: None,
+            screenshot_path: None,
+            camera: Camera::new(1.0), camera_bindings: None,
             start_time: Instant::now(), frame_count: 0, last_report: Instant::now(),
         }
     }
 
-    fn init_wgpu(&mut self, window: Arc<Window>) {
-        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
-            backends: wgpu::Backends::METAL,
-            ..Default::default()
-        });
+    /// Enables the post-processing filter chain described by the preset at
+    /// `path` (see [`post_process::parse_preset`] for the format). The
+    /// scene is then rendered into an offscreen texture and the chain
+    /// applied before presenting, instead of drawing straight to the
+    /// swapchain.
+    fn with_post_process_preset(mut self, path: impl Into<String>) -> Self {
+        self.post_process_preset = Some(path.into());
+        self
+    }
 
-        let surface = instance.create_surface(window.clone()).unwrap();
-        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
-            power_preference: wgpu::PowerPreference::HighPerformance,
-            compatible_surface: Some(&surface),
-            force_fallback_adapter: false,
-        })).expect("Failed to find adapter");
+    /// After the first frame renders successfully, renders one extra
+    /// offscreen frame and writes it to `path` as a PNG via
+    /// [`OffscreenRenderTarget::save_png`], then exits — useful for
+    /// regression-testing the leak-repro itself without a human watching
+    /// the window.
+    fn with_screenshot_path(mut self, path: impl Into<String>) -> Self {
+        self.screenshot_path = Some(path.into());
+        self
+    }
+
+    /// Tries `backend_config.preferred` first, then each fallback in order,
+    /// until one of them produces an adapter. Returns the instance/surface/
+    /// adapter for whichever backend succeeded.
+    fn request_adapter_with_fallback(
+        &self,
+        window: &Arc<Window>,
+    ) -> (wgpu::Instance, wgpu::Surface<'static>, wgpu::Adapter) {
+        let candidates = std::iter::once(self.backend_config.preferred)
+            .chain(self.backend_config.fallbacks.iter().copied());
+
+        for backend in candidates {
+            let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+                backends: backend.to_wgpu(),
+                ..Default::default()
+            });
+
+            let Ok(surface) = instance.create_surface(window.clone()) else { continue };
+
+            if let Some(adapter) = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+                power_preference: self.backend_config.power_preference,
+                compatible_surface: Some(&surface),
+                force_fallback_adapter: self.backend_config.force_fallback_adapter,
+            })) {
+                println!("Backend: {:?} (selected)", backend);
+                return (instance, surface, adapter);
+            }
+        }
+
+        panic!("Failed to find adapter: no backend in the preferred/fallback chain produced one");
+    }
+
+    fn init_wgpu(&mut self, window: Arc<Window>) {
+        let (_instance, surface, adapter) = self.request_adapter_with_fallback(&window);
 
         println!("Adapter: {:?}", adapter.get_info().name);
         println!("Backend: {:?}", adapter.get_info().backend);
@@ -61,21 +229,33 @@ impl App {
         };
         surface.configure(&device, &config);
 
+        self.camera.set_aspect(config.width, config.height);
+        let camera_bindings = CameraBindings::new(&device, config.desired_maximum_frame_latency as usize);
+
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Shader"),
             source: wgpu::ShaderSource::Wgsl(
-                "@vertex fn vs(@builtin(vertex_index) i: u32) -> @builtin(position) vec4f {
-                    var p = array<vec2f, 3>(vec2f(-1,-1), vec2f(3,-1), vec2f(-1,3));
-                    return vec4f(p[i], 0, 1);
+                "struct CameraUniform { view_proj: mat4x4<f32> }
+                @group(0) @binding(0) var<uniform> camera: CameraUniform;
+
+                @vertex fn vs(@builtin(vertex_index) i: u32) -> @builtin(position) vec4f {
+                    var p = array<vec3f, 3>(vec3f(-0.5,-0.5,0), vec3f(0.5,-0.5,0), vec3f(0,0.5,0));
+                    return camera.view_proj * vec4f(p[i], 1);
                 }
                 @fragment fn fs() -> @location(0) vec4f { return vec4f(0.1, 0.2, 0.3, 1); }"
                 .into(),
             ),
         });
 
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Pipeline Layout"),
+            bind_group_layouts: &[&camera_bindings.bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: Some("Pipeline"),
-            layout: None,
+            layout: Some(&pipeline_layout),
             vertex: wgpu::VertexState {
                 module: &shader,
                 entry_point: Some("vs"),
@@ -95,53 +275,132 @@ impl App {
             cache: None,
         });
 
+        let background_shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Background Shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                "struct VsOut { @builtin(position) pos: vec4f, @location(0) uv: vec2f }
+
+                @vertex fn vs(@builtin(vertex_index) i: u32) -> VsOut {
+                    var p = array<vec2f, 3>(vec2f(-1,-1), vec2f(3,-1), vec2f(-1,3));
+                    var out: VsOut;
+                    out.pos = vec4f(p[i], 0, 1);
+                    out.uv = p[i] * 0.5 + vec2f(0.5, 0.5);
+                    return out;
+                }
+                @fragment fn fs(in: VsOut) -> @location(0) vec4f {
+                    return vec4f(0.05, 0.05, 0.08 + 0.1 * in.uv.y, 1);
+                }"
+                .into(),
+            ),
+        });
+
+        let background_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Background Pipeline"),
+            layout: None,
+            vertex: wgpu::VertexState {
+                module: &background_shader,
+                entry_point: Some("vs"),
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &background_shader,
+                entry_point: Some("fs"),
+                targets: &[Some(config.format.into())],
+                compilation_options: Default::default(),
+            }),
+            primitive: Default::default(),
+            depth_stencil: None,
+            multisample: Default::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let mut renderer = Renderer::new(device, queue, config);
+        renderer.register_pass(Box::new(BackgroundPass { pipeline: background_pipeline }));
+        renderer.register_pass(Box::new(TrianglePass { pipeline }));
+        self.camera_bindings = Some(camera_bindings);
+
+        if let Some(preset_path) = &self.post_process_preset {
+            let preset = std::fs::read_to_string(preset_path)
+                .unwrap_or_else(|e| panic!("failed to read post-process preset {preset_path:?}: {e}"));
+            let mut chain = PostProcessChain::new(&renderer.device, post_process::parse_preset(&preset), renderer.config.format);
+
+            let viewport_size = (renderer.config.width, renderer.config.height);
+            let scene_target = OffscreenRenderTarget::new(&renderer.device, viewport_size.0, viewport_size.1, renderer.config.format);
+            let scene_view = scene_target.acquire().unwrap().view;
+            chain.rebuild(&renderer.device, &scene_view, viewport_size, viewport_size);
+
+            self.post_process = Some(chain);
+            self.scene_target = Some(scene_target);
+        }
+
         self.window = Some(window);
         self.surface = Some(surface);
-        self.device = Some(device);
-        self.queue = Some(queue);
-        self.config = Some(config);
-        self.render_pipeline = Some(render_pipeline);
+        self.renderer = Some(renderer);
 
         println!("\nRunning... Monitor memory with: ps -o rss= -p <pid>\n");
     }
 
-    fn render(&mut self) {
+    /// Renders one frame. Returns `false` once a one-shot `--screenshot`
+    /// capture has been written out, telling the caller to stop requesting
+    /// redraws and exit.
+    fn render(&mut self) -> bool {
         let surface = self.surface.as_ref().unwrap();
-        let device = self.device.as_ref().unwrap();
-        let queue = self.queue.as_ref().unwrap();
-        let pipeline = self.render_pipeline.as_ref().unwrap();
-
-        let Ok(frame) = surface.get_current_texture() else { return };
-        let view = frame.texture.create_view(&Default::default());
-        let mut encoder = device.create_command_encoder(&Default::default());
-
-        {
-            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                label: None,
-                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color { r: 0.1, g: 0.2, b: 0.3, a: 1.0 }),
-                        store: wgpu::StoreOp::Store,
-                    },
-                })],
-                depth_stencil_attachment: None,
-                timestamp_writes: None,
-                occlusion_query_set: None,
-            });
-            pass.set_pipeline(pipeline);
-            pass.draw(0..3, 0..1);
-        }
+        let renderer = self.renderer.as_ref().unwrap();
+        let camera_bindings = self.camera_bindings.as_ref().unwrap();
+        let camera_bind_group = camera_bindings.update(
+            &renderer.queue,
+            self.frame_count,
+            CameraUniform::from_camera(&self.camera),
+        );
+        let frame_data = FrameData {
+            frame_count: self.frame_count,
+            elapsed: self.start_time.elapsed(),
+            camera_bind_group,
+        };
 
-        queue.submit(std::iter::once(encoder.finish()));
-        frame.present();
+        let rendered = if let Some(chain) = &self.post_process {
+            let scene_target = self.scene_target.as_ref().unwrap();
+            if !renderer.render(scene_target, &frame_data) {
+                return true;
+            }
+
+            let swapchain_target = SurfaceRenderTarget { surface, config: &renderer.config };
+            let Some(final_frame) = swapchain_target.acquire() else { return true };
+
+            let mut encoder = renderer.device.create_command_encoder(&Default::default());
+            chain.record(&mut encoder, &final_frame.view);
+            renderer.queue.submit(std::iter::once(encoder.finish()));
+            final_frame.present();
+            true
+        } else {
+            let target = SurfaceRenderTarget { surface, config: &renderer.config };
+            renderer.render(&target, &frame_data)
+        };
+        if !rendered {
+            return true;
+        }
         self.frame_count += 1;
 
         if self.last_report.elapsed().as_secs() >= 10 {
             println!("[{:>4}s] {} frames", self.start_time.elapsed().as_secs(), self.frame_count);
             self.last_report = Instant::now();
         }
+
+        if let Some(path) = self.screenshot_path.take() {
+            let path = std::path::Path::new(&path);
+            let offscreen = OffscreenRenderTarget::new(
+                &renderer.device, renderer.config.width, renderer.config.height, renderer.config.format,
+            );
+            renderer.render(&offscreen, &frame_data);
+            offscreen.save_png(&renderer.device, &renderer.queue, path)
+                .unwrap_or_else(|e| panic!("failed to save screenshot to {path:?}: {e}"));
+            println!("Wrote screenshot to {}", path.display());
+            return false;
+        }
+
+        true
     }
 }
 
@@ -161,24 +420,98 @@ impl ApplicationHandler for App {
         match event {
             WindowEvent::CloseRequested => event_loop.exit(),
             WindowEvent::Resized(size) => {
-                if let (Some(s), Some(d), Some(c)) = (&self.surface, &self.device, &mut self.config) {
-                    c.width = size.width.max(1);
-                    c.height = size.height.max(1);
-                    s.configure(d, c);
+                self.camera.set_aspect(size.width, size.height);
+
+                if let (Some(s), Some(r)) = (&self.surface, &mut self.renderer) {
+                    r.resize(s, size.width, size.height);
+
+                    if let Some(chain) = &mut self.post_process {
+                        let viewport_size = (r.config.width, r.config.height);
+                        let scene_target = OffscreenRenderTarget::new(&r.device, viewport_size.0, viewport_size.1, r.config.format);
+                        let scene_view = scene_target.acquire().unwrap().view;
+                        chain.rebuild(&r.device, &scene_view, viewport_size, viewport_size);
+                        self.scene_target = Some(scene_target);
+                    }
+                }
+            }
+            WindowEvent::KeyboardInput {
+                event: KeyEvent { physical_key: PhysicalKey::Code(code), state: ElementState::Pressed, .. },
+                ..
+            } => {
+                const MOVE_STEP: f32 = 0.1;
+                const TURN_STEP: f32 = 0.05;
+                match code {
+                    KeyCode::KeyW => self.camera.position.z -= MOVE_STEP,
+                    KeyCode::KeyS => self.camera.position.z += MOVE_STEP,
+                    KeyCode::KeyA => self.camera.position.x -= MOVE_STEP,
+                    KeyCode::KeyD => self.camera.position.x += MOVE_STEP,
+                    KeyCode::ArrowLeft => self.camera.yaw -= TURN_STEP,
+                    KeyCode::ArrowRight => self.camera.yaw += TURN_STEP,
+                    KeyCode::ArrowUp => self.camera.pitch = (self.camera.pitch + TURN_STEP).clamp(-1.5, 1.5),
+                    KeyCode::ArrowDown => self.camera.pitch = (self.camera.pitch - TURN_STEP).clamp(-1.5, 1.5),
+                    _ => {}
                 }
             }
             WindowEvent::RedrawRequested => {
-                self.render();
-                self.window.as_ref().unwrap().request_redraw();
+                if self.render() {
+                    self.window.as_ref().unwrap().request_redraw();
+                } else {
+                    event_loop.exit();
+                }
             }
             _ => {}
         }
     }
 }
 
+/// Parses `--post-process <preset-path>`, `--screenshot <output-path>`,
+/// `--force-fallback-adapter`, and `--power-preference <low|high>` out of
+/// the process args and applies them to a fresh [`App`]. The fallback-
+/// adapter and power-preference flags exist so headless/CI runs can force
+/// a software adapter instead of depending on whatever the host happens
+/// to expose.
+fn app_from_args() -> App {
+    let mut backend_config = BackendConfig::default();
+    let mut post_process_preset = None;
+    let mut screenshot_path = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--post-process" => {
+                post_process_preset = Some(args.next().expect("--post-process requires a preset path"));
+            }
+            "--screenshot" => {
+                screenshot_path = Some(args.next().expect("--screenshot requires an output path"));
+            }
+            "--force-fallback-adapter" => {
+                backend_config.force_fallback_adapter = true;
+            }
+            "--power-preference" => {
+                let value = args.next().expect("--power-preference requires 'low' or 'high'");
+                backend_config.power_preference = match value.as_str() {
+                    "low" => wgpu::PowerPreference::LowPower,
+                    "high" => wgpu::PowerPreference::HighPerformance,
+                    other => panic!("--power-preference expects 'low' or 'high', got {other:?}"),
+                };
+            }
+            other => panic!("unrecognized argument: {other}"),
+        }
+    }
+
+    let mut app = App::with_backend_config(backend_config);
+    if let Some(path) = post_process_preset {
+        app = app.with_post_process_preset(path);
+    }
+    if let Some(path) = screenshot_path {
+        app = app.with_screenshot_path(path);
+    }
+    app
+}
+
 fn main() {
     env_logger::init();
     let event_loop = EventLoop::new().unwrap();
     event_loop.set_control_flow(ControlFlow::Poll);
-    event_loop.run_app(&mut App::new()).unwrap();
+    event_loop.run_app(&mut app_from_args()).unwrap();
 }
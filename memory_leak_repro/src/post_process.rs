@@ -0,0 +1,260 @@
+/// What a pass's output size is computed relative to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScaleRelativeTo {
+    Viewport,
+    Source,
+}
+
+/// One entry of a `.slangp`-style preset: a WGSL fragment shader plus how
+/// its output texture should be sized and sampled.
+#[derive(Debug, Clone)]
+pub struct PostProcessPassConfig {
+    pub shader_path: String,
+    pub scale: f32,
+    pub scale_relative_to: ScaleRelativeTo,
+    pub filter_mode: wgpu::FilterMode,
+}
+
+/// Parses a preset listing one pass per non-empty, non-comment line:
+///
+/// ```text
+/// shader=shaders/bloom.wgsl scale=1.0 relative=viewport filter=linear
+/// shader=shaders/crt.wgsl   scale=1.0 relative=source   filter=nearest
+/// ```
+pub fn parse_preset(contents: &str) -> Vec<PostProcessPassConfig> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(parse_preset_line)
+        .collect()
+}
+
+fn parse_preset_line(line: &str) -> PostProcessPassConfig {
+    let mut shader_path = String::new();
+    let mut scale = 1.0;
+    let mut scale_relative_to = ScaleRelativeTo::Viewport;
+    let mut filter_mode = wgpu::FilterMode::Linear;
+
+    for field in line.split_whitespace() {
+        let Some((key, value)) = field.split_once('=') else { continue };
+        match (key, value) {
+            ("shader", path) => shader_path = path.to_string(),
+            ("scale", n) => scale = n.parse().unwrap_or(1.0),
+            ("relative", "source") => scale_relative_to = ScaleRelativeTo::Source,
+            ("relative", "viewport") => scale_relative_to = ScaleRelativeTo::Viewport,
+            ("filter", "nearest") => filter_mode = wgpu::FilterMode::Nearest,
+            ("filter", "linear") => filter_mode = wgpu::FilterMode::Linear,
+            _ => {}
+        }
+    }
+
+    PostProcessPassConfig { shader_path, scale, scale_relative_to, filter_mode }
+}
+
+const FULLSCREEN_TRIANGLE_VS: &str = "
+@vertex fn vs(@builtin(vertex_index) i: u32) -> @builtin(position) vec4f {
+    var p = array<vec2f, 3>(vec2f(-1,-1), vec2f(3,-1), vec2f(-1,3));
+    return vec4f(p[i], 0, 1);
+}
+";
+
+struct PostProcessPassGpu {
+    pipeline: wgpu::RenderPipeline,
+    bind_group: wgpu::BindGroup,
+    // Kept alive alongside `output_view`; never read directly once built.
+    #[allow(dead_code)]
+    output_texture: Option<wgpu::Texture>,
+    output_view: Option<wgpu::TextureView>,
+}
+
+/// An ordered chain of fragment-shader passes applied after the scene has
+/// rendered into an offscreen texture, RetroArch-`.slangp`-style. Each pass
+/// samples the previous pass's output (or the original scene, for the
+/// first pass) and writes into its own intermediate texture; the last pass
+/// writes directly into whatever view `record` is given (typically the
+/// swapchain).
+pub struct PostProcessChain {
+    bind_group_layout: wgpu::BindGroupLayout,
+    pipeline_layout: wgpu::PipelineLayout,
+    vs_module: wgpu::ShaderModule,
+    format: wgpu::TextureFormat,
+    configs: Vec<PostProcessPassConfig>,
+    passes: Vec<PostProcessPassGpu>,
+}
+
+impl PostProcessChain {
+    pub fn new(device: &wgpu::Device, configs: Vec<PostProcessPassConfig>, format: wgpu::TextureFormat) -> Self {
+        assert!(
+            !configs.is_empty(),
+            "post-process preset has no passes (empty or all-comment file); record() has nothing to draw into the swapchain"
+        );
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("PostProcess BindGroupLayout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("PostProcess PipelineLayout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let vs_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("PostProcess Fullscreen Triangle VS"),
+            source: wgpu::ShaderSource::Wgsl(FULLSCREEN_TRIANGLE_VS.into()),
+        });
+
+        Self {
+            bind_group_layout,
+            pipeline_layout,
+            vs_module,
+            format,
+            configs,
+            passes: Vec::new(),
+        }
+    }
+
+    /// Builds (or rebuilds, e.g. after a resize) the intermediate textures
+    /// and bind groups for every configured pass. `scene_view`/`scene_size`
+    /// describe the freshly-rendered scene texture that feeds the first
+    /// pass; `viewport_size` is what `scale`-relative-to-`Viewport` passes
+    /// size themselves against.
+    pub fn rebuild(
+        &mut self,
+        device: &wgpu::Device,
+        scene_view: &wgpu::TextureView,
+        scene_size: (u32, u32),
+        viewport_size: (u32, u32),
+    ) {
+        let mut passes = Vec::with_capacity(self.configs.len());
+        let mut input_view = scene_view.clone();
+        let mut input_size = scene_size;
+        let last_index = self.configs.len().saturating_sub(1);
+
+        for (i, cfg) in self.configs.iter().enumerate() {
+            let shader_source = std::fs::read_to_string(&cfg.shader_path)
+                .unwrap_or_else(|e| panic!("failed to read post-process shader {:?}: {e}", cfg.shader_path));
+            let fs_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: Some(&cfg.shader_path),
+                source: wgpu::ShaderSource::Wgsl(shader_source.into()),
+            });
+
+            let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some("PostProcess Pipeline"),
+                layout: Some(&self.pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &self.vs_module,
+                    entry_point: Some("vs"),
+                    buffers: &[],
+                    compilation_options: Default::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &fs_module,
+                    entry_point: Some("fs"),
+                    targets: &[Some(self.format.into())],
+                    compilation_options: Default::default(),
+                }),
+                primitive: Default::default(),
+                depth_stencil: None,
+                multisample: Default::default(),
+                multiview: None,
+                cache: None,
+            });
+
+            let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+                label: Some("PostProcess Sampler"),
+                mag_filter: cfg.filter_mode,
+                min_filter: cfg.filter_mode,
+                ..Default::default()
+            });
+
+            let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("PostProcess BindGroup"),
+                layout: &self.bind_group_layout,
+                entries: &[
+                    wgpu::BindGroupEntry { binding: 0, resource: wgpu::BindingResource::TextureView(&input_view) },
+                    wgpu::BindGroupEntry { binding: 1, resource: wgpu::BindingResource::Sampler(&sampler) },
+                ],
+            });
+
+            let base = match cfg.scale_relative_to {
+                ScaleRelativeTo::Viewport => viewport_size,
+                ScaleRelativeTo::Source => input_size,
+            };
+            let output_size = (
+                ((base.0 as f32) * cfg.scale).round().max(1.0) as u32,
+                ((base.1 as f32) * cfg.scale).round().max(1.0) as u32,
+            );
+
+            let is_last = i == last_index;
+            let (output_texture, output_view) = if is_last {
+                (None, None)
+            } else {
+                let texture = device.create_texture(&wgpu::TextureDescriptor {
+                    label: Some("PostProcess Intermediate"),
+                    size: wgpu::Extent3d { width: output_size.0, height: output_size.1, depth_or_array_layers: 1 },
+                    mip_level_count: 1,
+                    sample_count: 1,
+                    dimension: wgpu::TextureDimension::D2,
+                    format: self.format,
+                    usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+                    view_formats: &[],
+                });
+                let view = texture.create_view(&Default::default());
+                (Some(texture), Some(view))
+            };
+
+            if let Some(view) = &output_view {
+                input_view = view.clone();
+                input_size = output_size;
+            }
+
+            passes.push(PostProcessPassGpu { pipeline, bind_group, output_texture, output_view });
+        }
+
+        self.passes = passes;
+    }
+
+    /// Records every pass into `encoder` in sequence. The last pass targets
+    /// `final_view` (the swapchain); earlier passes target their own owned
+    /// intermediate textures built by `rebuild`.
+    pub fn record(&self, encoder: &mut wgpu::CommandEncoder, final_view: &wgpu::TextureView) {
+        let last_index = self.passes.len().saturating_sub(1);
+        for (i, pass) in self.passes.iter().enumerate() {
+            let view = if i == last_index { final_view } else { pass.output_view.as_ref().unwrap() };
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("PostProcess Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target: None,
+                    ops: wgpu::Operations { load: wgpu::LoadOp::Clear(wgpu::Color::BLACK), store: wgpu::StoreOp::Store },
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+            render_pass.set_pipeline(&pass.pipeline);
+            render_pass.set_bind_group(0, &pass.bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+    }
+}
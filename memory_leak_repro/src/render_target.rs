@@ -0,0 +1,164 @@
+/// Something a [`crate::renderer::Renderer`] can draw into: the on-screen
+/// swapchain surface, or an owned offscreen texture for screenshots,
+/// thumbnails, and headless tests.
+pub trait RenderTarget {
+    fn size(&self) -> (u32, u32);
+    fn format(&self) -> wgpu::TextureFormat;
+
+    /// Acquires the view to draw into this frame. Returns `None` if the
+    /// target is temporarily unavailable (e.g. a surface that was lost
+    /// mid-resize), mirroring `Surface::get_current_texture`'s `Result`.
+    fn acquire(&self) -> Option<RenderTargetFrame>;
+}
+
+/// A single acquired frame: the view to draw into, plus whatever this
+/// target needs to finish the frame.
+pub struct RenderTargetFrame {
+    pub view: wgpu::TextureView,
+    surface_texture: Option<wgpu::SurfaceTexture>,
+}
+
+impl RenderTargetFrame {
+    /// Presents the frame if this target is on-screen; a no-op for
+    /// offscreen targets.
+    pub fn present(self) {
+        if let Some(texture) = self.surface_texture {
+            texture.present();
+        }
+    }
+}
+
+/// Renders to the window's swapchain.
+pub struct SurfaceRenderTarget<'a> {
+    pub surface: &'a wgpu::Surface<'static>,
+    pub config: &'a wgpu::SurfaceConfiguration,
+}
+
+impl RenderTarget for SurfaceRenderTarget<'_> {
+    fn size(&self) -> (u32, u32) {
+        (self.config.width, self.config.height)
+    }
+
+    fn format(&self) -> wgpu::TextureFormat {
+        self.config.format
+    }
+
+    fn acquire(&self) -> Option<RenderTargetFrame> {
+        let surface_texture = self.surface.get_current_texture().ok()?;
+        let view = surface_texture.texture.create_view(&Default::default());
+        Some(RenderTargetFrame { view, surface_texture: Some(surface_texture) })
+    }
+}
+
+/// Renders to an owned offscreen texture, with CPU readback so the result
+/// can be written out as a PNG (useful for regression-testing rendering
+/// itself, or for thumbnails that never touch a window).
+pub struct OffscreenRenderTarget {
+    texture: wgpu::Texture,
+    width: u32,
+    height: u32,
+    format: wgpu::TextureFormat,
+}
+
+impl OffscreenRenderTarget {
+    pub fn new(device: &wgpu::Device, width: u32, height: u32, format: wgpu::TextureFormat) -> Self {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Offscreen Render Target"),
+            size: wgpu::Extent3d { width: width.max(1), height: height.max(1), depth_or_array_layers: 1 },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            // RENDER_ATTACHMENT so passes can draw into it, COPY_SRC so
+            // `read_back` can pull pixels out, TEXTURE_BINDING so it can
+            // also feed a post-processing chain as the scene's input.
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::COPY_SRC
+                | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        Self { texture, width: width.max(1), height: height.max(1), format }
+    }
+
+    /// Copies the rendered texture into a CPU buffer and maps it, returning
+    /// tightly packed rows of 4-byte texels in the target's own format (so
+    /// BGRA order for `Bgra8*` targets; wgpu's row-alignment padding is
+    /// stripped).
+    pub fn read_back(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> Vec<u8> {
+        let bytes_per_pixel = 4u32;
+        let unpadded_bytes_per_row = self.width * bytes_per_pixel;
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(align) * align;
+
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Offscreen Readback Buffer"),
+            size: (padded_bytes_per_row * self.height) as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = device.create_command_encoder(&Default::default());
+        encoder.copy_texture_to_buffer(
+            self.texture.as_image_copy(),
+            wgpu::TexelCopyBufferInfo {
+                buffer: &buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: Some(self.height),
+                },
+            },
+            wgpu::Extent3d { width: self.width, height: self.height, depth_or_array_layers: 1 },
+        );
+        queue.submit(std::iter::once(encoder.finish()));
+
+        let slice = buffer.slice(..);
+        let (tx, rx) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = tx.send(result);
+        });
+        device.poll(wgpu::Maintain::Wait);
+        rx.recv().expect("readback map callback dropped").expect("failed to map readback buffer");
+
+        let mapped = slice.get_mapped_range();
+        let mut pixels = Vec::with_capacity((unpadded_bytes_per_row * self.height) as usize);
+        for row in mapped.chunks(padded_bytes_per_row as usize) {
+            pixels.extend_from_slice(&row[..unpadded_bytes_per_row as usize]);
+        }
+        drop(mapped);
+        buffer.unmap();
+        pixels
+    }
+
+    /// Reads this target back and writes it to `path` as a PNG, via
+    /// [`Self::read_back`]. Swaps channel order first for BGRA-ordered
+    /// formats (the common case when `format` was copied from a swapchain
+    /// surface) since `image` expects RGBA.
+    pub fn save_png(&self, device: &wgpu::Device, queue: &wgpu::Queue, path: &std::path::Path) -> image::ImageResult<()> {
+        let (width, height) = self.size();
+        let mut pixels = self.read_back(device, queue);
+
+        if matches!(self.format(), wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb) {
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+
+        image::save_buffer(path, &pixels, width, height, image::ColorType::Rgba8)
+    }
+}
+
+impl RenderTarget for OffscreenRenderTarget {
+    fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+
+    fn acquire(&self) -> Option<RenderTargetFrame> {
+        let view = self.texture.create_view(&Default::default());
+        Some(RenderTargetFrame { view, surface_texture: None })
+    }
+}
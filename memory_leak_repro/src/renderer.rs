@@ -0,0 +1,169 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rayon::prelude::*;
+
+use crate::render_target::RenderTarget;
+
+/// Coarse ordering bucket for a [`RenderPass`]. Passes are grouped by phase
+/// and recorded in this fixed order every frame, regardless of registration
+/// order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Phase {
+    Background,
+    Opaque,
+    Transparent,
+    Overlay,
+}
+
+const PHASE_ORDER: [Phase; 4] = [Phase::Background, Phase::Opaque, Phase::Transparent, Phase::Overlay];
+
+/// Per-frame context handed to every pass's `record` call, including the
+/// bind group for whichever in-flight camera uniform slot this frame uses.
+pub struct FrameData<'a> {
+    pub frame_count: u64,
+    pub elapsed: Duration,
+    pub camera_bind_group: &'a wgpu::BindGroup,
+}
+
+/// A single unit of rendering work. Implementors open their own render pass
+/// against `view` (using `LoadOp::Load`, since the renderer clears the view
+/// once up front) and record whatever draw calls they need into `encoder`.
+///
+/// `Send + Sync` because passes are recorded from rayon worker threads (see
+/// [`Renderer::render`]), each against its own secondary `CommandEncoder`.
+pub trait RenderPass: Send + Sync {
+    fn phase(&self) -> Phase;
+    fn record(&self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView, frame: &FrameData<'_>);
+}
+
+/// Owns the wgpu device/queue/surface configuration and the registry of
+/// passes that draw into a frame. Users extend the picture by registering
+/// passes instead of editing a hardcoded `render` function.
+///
+/// `device` is `Arc`-wrapped so it can be shared across threads; the parallel
+/// recording in `render` borrows it from worker threads through `&self` to
+/// create secondary command encoders.
+pub struct Renderer {
+    pub device: Arc<wgpu::Device>,
+    pub queue: wgpu::Queue,
+    pub config: wgpu::SurfaceConfiguration,
+    pub clear_color: wgpu::Color,
+    passes: Vec<Box<dyn RenderPass>>,
+}
+
+impl Renderer {
+    pub fn new(device: wgpu::Device, queue: wgpu::Queue, config: wgpu::SurfaceConfiguration) -> Self {
+        Self {
+            device: Arc::new(device),
+            queue,
+            config,
+            clear_color: wgpu::Color { r: 0.1, g: 0.2, b: 0.3, a: 1.0 },
+            passes: Vec::new(),
+        }
+    }
+
+    pub fn register_pass(&mut self, pass: Box<dyn RenderPass>) {
+        self.passes.push(pass);
+    }
+
+    pub fn resize(&mut self, surface: &wgpu::Surface, width: u32, height: u32) {
+        self.config.width = width.max(1);
+        self.config.height = height.max(1);
+        surface.configure(&self.device, &self.config);
+    }
+
+    /// Acquires a frame from `target`, records every registered pass
+    /// against it (grouped and ordered by `Phase`), submits the resulting
+    /// command buffers in one `queue.submit` call, and presents the frame.
+    /// Returns `false` if `target` had no frame available (e.g. a surface
+    /// lost mid-resize). The same pass code runs whether `target` is the
+    /// swapchain or an offscreen texture.
+    ///
+    /// Passes are recorded in parallel: after flattening phase-grouped
+    /// passes into a single ordered list, the list is split into up to
+    /// `rayon::current_num_threads()` contiguous chunks (at least 2 when
+    /// there are two or more passes, never more than one chunk per pass),
+    /// and each chunk is
+    /// recorded by a worker thread into its own secondary `CommandEncoder`
+    /// (`wgpu::Device` is `Send + Sync`, so workers share `self.device`
+    /// directly). Chunks stay contiguous and are resubmitted in their
+    /// original index order, so phase ordering is preserved even though
+    /// the recording itself ran out of order across threads. This only
+    /// changes *when CPU-side recording happens*; the GPU still sees one
+    /// `queue.submit` per frame, so it doesn't add to the
+    /// `desired_maximum_frame_latency`-controlled frames-in-flight budget
+    /// set up in `init_wgpu`.
+    pub fn render(&self, target: &impl RenderTarget, frame: &FrameData<'_>) -> bool {
+        let Some(target_frame) = target.acquire() else { return false };
+        let view = &target_frame.view;
+
+        let mut by_phase: BTreeMap<Phase, Vec<&Box<dyn RenderPass>>> = BTreeMap::new();
+        for pass in &self.passes {
+            by_phase.entry(pass.phase()).or_default().push(pass);
+        }
+        let ordered: Vec<&Box<dyn RenderPass>> = PHASE_ORDER
+            .iter()
+            .filter_map(|phase| by_phase.get(phase))
+            .flat_map(|passes| passes.iter().copied())
+            .collect();
+
+        // The clear has to land before any pass's draws, so it gets its own
+        // leading command buffer rather than a chunk slot.
+        let mut clear_encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Clear"),
+        });
+        clear_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Clear"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(self.clear_color),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        // `current_num_threads()` alone would collapse to a single chunk on
+        // any 1-thread host (this series' own sandbox included), which
+        // means the sort-by-chunk-index reassembly below would never run
+        // against more than one chunk. Floor it at 2 whenever there's more
+        // than one pass to split, so the multi-chunk path is exercised
+        // regardless of the ambient thread count.
+        let num_chunks = if ordered.len() >= 2 {
+            rayon::current_num_threads().max(2).min(ordered.len())
+        } else {
+            1
+        };
+        let chunk_size = ordered.len().div_ceil(num_chunks).max(1);
+
+        let mut chunk_buffers: Vec<(usize, wgpu::CommandBuffer)> = ordered
+            .chunks(chunk_size)
+            .enumerate()
+            .collect::<Vec<_>>()
+            .into_par_iter()
+            .map(|(chunk_index, passes)| {
+                let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Pass Chunk"),
+                });
+                for pass in passes {
+                    pass.record(&mut encoder, view, frame);
+                }
+                (chunk_index, encoder.finish())
+            })
+            .collect();
+        chunk_buffers.sort_by_key(|(index, _)| *index);
+
+        let buffers = std::iter::once(clear_encoder.finish())
+            .chain(chunk_buffers.into_iter().map(|(_, buffer)| buffer));
+        self.queue.submit(buffers);
+
+        target_frame.present();
+        true
+    }
+}